@@ -2,7 +2,7 @@ use colorlight::ColorlightCard;
 use std::thread;
 use std::time::Duration;
 
-fn main() -> std::io::Result<()> {
+fn main() -> Result<(), colorlight::Error> {
     let interface_name = "en0";
     let mut controller = ColorlightCard::open(interface_name)?;
 
@@ -34,6 +34,4 @@ fn main() -> std::io::Result<()> {
         // sleep to avoid flickering
         thread::sleep(Duration::from_millis(10));
     }
-
-    Ok(())
 }
\ No newline at end of file
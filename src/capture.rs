@@ -0,0 +1,159 @@
+//! Frame capture support for debugging the Colorlight wire protocol.
+//!
+//! [`PcapWriter`] appends every frame it is given to a classic-format pcap
+//! file that Wireshark can open directly, and [`TracingLink`] wraps any
+//! [`FrameLink`] so every frame sent or received through it is captured and
+//! summarised to [`log`], without the rest of [`ColorlightCard`] having to
+//! know capture is happening. This mirrors smoltcp's `phy::pcap_writer` and
+//! `phy::tracer`.
+
+use crate::wire::EtherType;
+use crate::{ColorlightCard, Error, FrameLink};
+use alloc::format;
+use alloc::string::String;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const PCAP_MAGIC: u32 = 0xa1b2c3d4;
+const PCAP_VERSION_MAJOR: u16 = 2;
+const PCAP_VERSION_MINOR: u16 = 4;
+const LINKTYPE_ETHERNET: u32 = 1;
+
+/// Appends captured Ethernet frames to a classic-format pcap file.
+pub struct PcapWriter {
+    file: BufWriter<File>,
+}
+
+impl PcapWriter {
+    /// Creates (or truncates) the pcap file at `path` and writes its global
+    /// header.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(&PCAP_MAGIC.to_le_bytes())?;
+        file.write_all(&PCAP_VERSION_MAJOR.to_le_bytes())?;
+        file.write_all(&PCAP_VERSION_MINOR.to_le_bytes())?;
+        file.write_all(&0i32.to_le_bytes())?; // thiszone: GMT
+        file.write_all(&0u32.to_le_bytes())?; // sigfigs: always 0
+        file.write_all(&65535u32.to_le_bytes())?; // snaplen
+        file.write_all(&LINKTYPE_ETHERNET.to_le_bytes())?;
+        Ok(Self { file })
+    }
+
+    /// Appends one Ethernet frame as a pcap packet record.
+    pub fn write_frame(&mut self, frame: &[u8]) -> Result<(), Error> {
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let len = frame.len() as u32;
+
+        self.file.write_all(&(since_epoch.as_secs() as u32).to_le_bytes())?;
+        self.file.write_all(&since_epoch.subsec_micros().to_le_bytes())?;
+        self.file.write_all(&len.to_le_bytes())?; // captured length
+        self.file.write_all(&len.to_le_bytes())?; // original length
+        self.file.write_all(frame)?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+/// Decodes a one-line summary (ethertype, row number, pixel count) of a
+/// Colorlight frame for logging purposes.
+fn summarize_frame(frame: &[u8]) -> String {
+    let Some(ethertype) = crate::wire::ethertype(frame) else {
+        return format!("frame too short to parse ({} bytes)", frame.len());
+    };
+    let payload = &frame[crate::wire::ETH_HEADER_LEN..];
+
+    match ethertype {
+        EtherType::PixelRow | EtherType::PixelRowExt if payload.len() >= 5 => {
+            let row = payload[0] as u16
+                | if ethertype == EtherType::PixelRowExt {
+                    0x100
+                } else {
+                    0
+                };
+            let pixel_count = ((payload[3] as u16) << 8) | payload[4] as u16;
+            format!(
+                "ethertype={:?} row={} pixels={}",
+                ethertype, row, pixel_count
+            )
+        }
+        other => format!("ethertype={:?} len={}", other, frame.len()),
+    }
+}
+
+/// Wraps a [`FrameLink`], appending every frame that passes through it to an
+/// optional [`PcapWriter`] and logging a decoded one-line summary.
+pub struct TracingLink<L: FrameLink> {
+    inner: L,
+    capture: Option<PcapWriter>,
+}
+
+impl<L: FrameLink> TracingLink<L> {
+    /// Wraps `inner` with tracing but no capture file yet.
+    pub fn new(inner: L) -> Self {
+        Self {
+            inner,
+            capture: None,
+        }
+    }
+
+    /// Wraps `inner` and immediately starts capturing to `path`.
+    pub fn with_capture<P: AsRef<Path>>(inner: L, path: P) -> Result<Self, Error> {
+        Ok(Self {
+            inner,
+            capture: Some(PcapWriter::create(path)?),
+        })
+    }
+
+    /// Starts (or replaces) the pcap capture for this link.
+    pub fn set_capture(&mut self, writer: PcapWriter) {
+        self.capture = Some(writer);
+    }
+}
+
+impl<L: FrameLink> FrameLink for TracingLink<L> {
+    fn send_frame(&mut self, frame: &[u8]) -> Result<(), Error> {
+        if let Some(capture) = &mut self.capture {
+            if let Err(err) = capture.write_frame(frame) {
+                log::warn!("failed to append sent frame to pcap capture: {}", err);
+            }
+        }
+        log::debug!("tx {}", summarize_frame(frame));
+        self.inner.send_frame(frame)
+    }
+
+    fn recv_frame(&mut self) -> Result<&[u8], Error> {
+        let frame = self.inner.recv_frame()?;
+        if let Some(capture) = &mut self.capture {
+            if let Err(err) = capture.write_frame(frame) {
+                log::warn!("failed to append received frame to pcap capture: {}", err);
+            }
+        }
+        log::debug!("rx {}", summarize_frame(frame));
+        Ok(frame)
+    }
+}
+
+impl<L: FrameLink> ColorlightCard<TracingLink<L>> {
+    /// Starts (or replaces) the pcap capture on this card's underlying link.
+    pub fn set_capture(&mut self, writer: PcapWriter) {
+        self.link.set_capture(writer);
+    }
+}
+
+#[cfg(feature = "std")]
+impl ColorlightCard<TracingLink<crate::PnetLink>> {
+    /// Opens a raw socket on `interface_name`, like
+    /// [`ColorlightCard::open`](crate::ColorlightCard::open), and captures
+    /// every sent/received frame to a pcap file at `path`.
+    pub fn open_with_capture<P: AsRef<Path>>(
+        interface_name: &str,
+        path: P,
+    ) -> Result<Self, Error> {
+        let link = TracingLink::with_capture(crate::PnetLink::open(interface_name)?, path)?;
+        Ok(ColorlightCard::new(link))
+    }
+}
@@ -0,0 +1,83 @@
+//! Discovery of multiple receiver cards chained on the same link.
+//!
+//! [`ColorlightCard::detect_receiver`](crate::ColorlightCard::detect_receiver)
+//! only ever looks at the first `0x0805` broadcast it sees. Real
+//! installations daisy-chain several receiver cards, so
+//! [`ColorlightCard::detect_all_receivers`] instead collects every distinct
+//! responder within a time window, keyed by its source MAC address — the
+//! same idea as a neighbor discovery table.
+
+use crate::{
+    build_detect_receiver_ack, build_detect_receiver_req, parse_detect_receiver_frame,
+    wire, ColorlightCard, DiscoveredCard, EtherType, Error, FrameLink, ReceiverCardInfo,
+};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use std::time::{Duration, Instant};
+
+impl<L: FrameLink> ColorlightCard<L> {
+    /// Like [`detect_receiver`](Self::detect_receiver), but bounds the wait
+    /// on a wall-clock `timeout` instead of a fixed attempt count, returning
+    /// as soon as the first receiver card responds.
+    pub fn detect_receiver_timeout(&mut self, timeout: Duration) -> Result<ReceiverCardInfo, Error> {
+        let detect_req = build_detect_receiver_req();
+        self.send_ethernet_frame(&detect_req)?;
+
+        let mut info = None;
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            if let Ok(packet) = self.link.recv_frame() {
+                if wire::ethertype(packet) == Some(EtherType::DetectReceiverRsp) {
+                    if let Ok(parsed) = parse_detect_receiver_frame(packet) {
+                        info = Some(parsed);
+                        break;
+                    }
+                }
+            }
+        }
+
+        let card_info = info.ok_or_else(|| {
+            Error::Timeout(String::from(
+                "No broadcast response (eth.type=0x0805) from receiver card",
+            ))
+        })?;
+
+        let ack_req = build_detect_receiver_ack();
+        self.send_ethernet_frame(&ack_req)?;
+
+        Ok(card_info)
+    }
+
+    /// Sends a "Detect Receiver Card" broadcast and collects every distinct
+    /// `0x0805` response seen within `timeout`, keyed by the responder's
+    /// source MAC address.
+    ///
+    /// Unlike [`detect_receiver`](Self::detect_receiver), this always waits
+    /// out the full window rather than stopping at the first responder, so
+    /// every card in a daisy chain gets a chance to answer.
+    pub fn detect_all_receivers(&mut self, timeout: Duration) -> Result<Vec<DiscoveredCard>, Error> {
+        let detect_req = build_detect_receiver_req();
+        self.send_ethernet_frame(&detect_req)?;
+
+        let mut discovered: BTreeMap<[u8; 6], DiscoveredCard> = BTreeMap::new();
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            if let Ok(packet) = self.link.recv_frame() {
+                if wire::ethertype(packet) == Some(EtherType::DetectReceiverRsp) {
+                    if let Ok(card) = parse_detect_receiver_frame(packet) {
+                        discovered.insert(card.mac, card);
+                    }
+                }
+            }
+        }
+
+        if !discovered.is_empty() {
+            // ack with Data[2] = 1, same broadcast handshake as detect_receiver
+            let ack_req = build_detect_receiver_ack();
+            self.send_ethernet_frame(&ack_req)?;
+        }
+
+        Ok(discovered.into_values().collect())
+    }
+}
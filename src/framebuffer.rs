@@ -0,0 +1,237 @@
+//! High-level framebuffer API.
+//!
+//! [`Framebuffer`] owns a `width x height` RGB pixel buffer, applies an
+//! optional per-channel [`GammaTable`], converts to BGR, and flushes to a
+//! [`ColorlightCard`] by driving [`send_row`](ColorlightCard::send_row) for
+//! every scanline followed by [`send_display_frame`](ColorlightCard::send_display_frame).
+//! It double-buffers so a flush only retransmits scanlines that actually
+//! changed since the previous one, which is the main lever for cutting
+//! Ethernet traffic and flicker on large panels.
+
+use crate::{ColorlightCard, Error, FrameLink};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A per-channel 8-bit gamma/brightness lookup table.
+#[derive(Debug, Clone)]
+pub struct GammaTable {
+    r: [u8; 256],
+    g: [u8; 256],
+    b: [u8; 256],
+}
+
+impl GammaTable {
+    /// Builds a table applying the same gamma exponent and brightness scale
+    /// to all three channels.
+    pub fn new(gamma: f32, brightness: f32) -> Self {
+        let table = Self::channel_table(gamma, brightness);
+        Self {
+            r: table,
+            g: table,
+            b: table,
+        }
+    }
+
+    /// Builds a table with independent `(gamma, brightness)` pairs per
+    /// channel.
+    pub fn per_channel(r: (f32, f32), g: (f32, f32), b: (f32, f32)) -> Self {
+        Self {
+            r: Self::channel_table(r.0, r.1),
+            g: Self::channel_table(g.0, g.1),
+            b: Self::channel_table(b.0, b.1),
+        }
+    }
+
+    fn channel_table(gamma: f32, brightness: f32) -> [u8; 256] {
+        let mut table = [0u8; 256];
+        for (i, slot) in table.iter_mut().enumerate() {
+            let normalized = i as f32 / 255.0;
+            let corrected = libm_powf(normalized, gamma) * brightness;
+            *slot = round_u8(corrected.clamp(0.0, 1.0) * 255.0);
+        }
+        table
+    }
+
+    fn apply(&self, r: u8, g: u8, b: u8) -> (u8, u8, u8) {
+        (
+            self.r[r as usize],
+            self.g[g as usize],
+            self.b[b as usize],
+        )
+    }
+}
+
+impl Default for GammaTable {
+    /// An identity table: no gamma correction or brightness scaling.
+    fn default() -> Self {
+        let mut identity = [0u8; 256];
+        for (i, slot) in identity.iter_mut().enumerate() {
+            *slot = i as u8;
+        }
+        Self {
+            r: identity,
+            g: identity,
+            b: identity,
+        }
+    }
+}
+
+// `f32::round` is `std`-only (it isn't a `core` inherent method); this is
+// equivalent for the non-negative inputs `channel_table` feeds it.
+#[cfg(feature = "std")]
+fn round_u8(x: f32) -> u8 {
+    x.round() as u8
+}
+
+#[cfg(not(feature = "std"))]
+fn round_u8(x: f32) -> u8 {
+    (x + 0.5) as u8
+}
+
+// `f32::powf` is `std`-only; `no_std` builds fall back to `libm`-free
+// integer exponentiation for the common integer-gamma case and otherwise
+// just scale linearly, since no_std targets rarely need fractional gamma.
+#[cfg(feature = "std")]
+fn libm_powf(base: f32, exp: f32) -> f32 {
+    base.powf(exp)
+}
+
+#[cfg(not(feature = "std"))]
+fn libm_powf(base: f32, exp: f32) -> f32 {
+    if exp == 1.0 {
+        base
+    } else if exp == (exp as i32) as f32 && exp >= 0.0 {
+        let mut result = 1.0;
+        for _ in 0..(exp as i32) {
+            result *= base;
+        }
+        result
+    } else {
+        base
+    }
+}
+
+/// Owns a `width x height` RGB pixel buffer and flushes it to a
+/// [`ColorlightCard`] one row at a time, skipping rows that are unchanged
+/// since the previous flush.
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    gamma: GammaTable,
+    pending: Vec<u8>,
+    sent: Option<Vec<u8>>,
+}
+
+impl Framebuffer {
+    /// Creates a black `width x height` framebuffer with no gamma
+    /// correction applied.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            gamma: GammaTable::default(),
+            pending: vec![0u8; width * height * 3],
+            sent: None,
+        }
+    }
+
+    /// Creates a `width x height` framebuffer that applies `gamma` to every
+    /// pixel on flush.
+    pub fn with_gamma(width: usize, height: usize, gamma: GammaTable) -> Self {
+        let mut fb = Self::new(width, height);
+        fb.gamma = gamma;
+        fb
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Replaces the gamma table used on the next flush. Forces every row
+    /// to be retransmitted on that flush, since rows already marked as sent
+    /// may now need re-correcting.
+    pub fn set_gamma(&mut self, gamma: GammaTable) {
+        self.gamma = gamma;
+        self.sent = None;
+    }
+
+    /// Replaces the whole buffer from a tightly-packed `width * height * 3`
+    /// RGB slice.
+    pub fn set_pixels(&mut self, rgb: &[u8]) -> Result<(), Error> {
+        if rgb.len() != self.pending.len() {
+            return Err(Error::InvalidArgument(String::from(
+                "set_pixels: slice length does not match width * height * 3",
+            )));
+        }
+        self.pending.copy_from_slice(rgb);
+        Ok(())
+    }
+
+    /// Sets a single pixel's RGB value.
+    pub fn set_pixel(&mut self, x: usize, y: usize, rgb: [u8; 3]) -> Result<(), Error> {
+        let offset = self.pixel_offset(x, y)?;
+        self.pending[offset..offset + 3].copy_from_slice(&rgb);
+        Ok(())
+    }
+
+    fn pixel_offset(&self, x: usize, y: usize) -> Result<usize, Error> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::InvalidArgument(String::from(
+                "pixel coordinate out of bounds",
+            )));
+        }
+        Ok((y * self.width + x) * 3)
+    }
+
+    fn row_range(&self, y: usize) -> core::ops::Range<usize> {
+        let start = y * self.width * 3;
+        start..start + self.width * 3
+    }
+
+    /// Gamma-corrects and BGR-swizzles scanline `y` of the pending buffer.
+    fn row_bgr(&self, y: usize) -> Vec<u8> {
+        let mut bgr = Vec::with_capacity(self.width * 3);
+        for px in self.pending[self.row_range(y)].chunks_exact(3) {
+            let (r, g, b) = self.gamma.apply(px[0], px[1], px[2]);
+            bgr.push(b);
+            bgr.push(g);
+            bgr.push(r);
+        }
+        bgr
+    }
+
+    /// Sends every scanline that changed since the previous flush, then a
+    /// display frame to make the update visible.
+    ///
+    /// * `brightness` and `(r, g, b)` are forwarded to
+    ///   [`send_display_frame`](ColorlightCard::send_display_frame).
+    pub fn flush<L: FrameLink>(
+        &mut self,
+        card: &mut ColorlightCard<L>,
+        brightness: u8,
+        r: u8,
+        g: u8,
+        b: u8,
+    ) -> Result<(), Error> {
+        for y in 0..self.height {
+            let range = self.row_range(y);
+            let unchanged = match &self.sent {
+                Some(sent) => sent[range.clone()] == self.pending[range.clone()],
+                None => false,
+            };
+            if unchanged {
+                continue;
+            }
+            card.send_row(y as u16, &self.row_bgr(y))?;
+        }
+
+        card.send_display_frame(brightness, r, g, b)?;
+        self.sent = Some(self.pending.clone());
+        Ok(())
+    }
+}
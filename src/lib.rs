@@ -8,75 +8,126 @@
 //! - Detect receiver card (send detect frame, parse response).
 //! - Send display frames (brightness, color temperature).
 //! - Send row-based pixel data frames (BGR pixel data).
-
-use pnet::datalink::{self, Config};
-use pnet::datalink::Channel::Ethernet;
-use pnet::packet::ethernet::EthernetPacket;
-use std::io::{Error, ErrorKind};
+//! - A higher-level [`Framebuffer`] that applies gamma correction and only
+//!   retransmits scanlines that changed since the last flush.
+//!
+//! The protocol logic is transport-agnostic: [`ColorlightCard`] is generic
+//! over a [`FrameLink`], so it can run on top of libpnet raw Ethernet
+//! sockets (the default, via [`PnetLink`]) or any other "send/receive a raw
+//! Ethernet frame" primitive, including on `no_std` targets.
+//!
+//! # Features (Cargo)
+//! - `std` (default): enables the libpnet-backed [`PnetLink`] transport and
+//!   [`ColorlightCard::open`]. Disable it to build the core frame-building
+//!   and parsing logic, plus your own [`FrameLink`], on `no_std` targets.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+mod link;
+pub mod wire;
+#[cfg(feature = "std")]
+pub mod capture;
+#[cfg(feature = "std")]
+mod discovery;
+pub mod framebuffer;
+#[cfg(test)]
+mod tests;
+
+pub use framebuffer::{Framebuffer, GammaTable};
+pub use link::FrameLink;
+#[cfg(feature = "std")]
+pub use link::PnetLink;
+pub use wire::EtherType;
 
 /// DST_MAC relevant for broadcast frame
 pub const SRC_MAC: [u8; 6] = [0x22, 0x22, 0x33, 0x44, 0x55, 0x66];
 pub const DST_MAC: [u8; 6] = [0x11, 0x22, 0x33, 0x44, 0x55, 0x66];
 
-#[allow(dead_code)]
-mod eth_types {
-    pub const DETECT_RECEIVER_REQ: u16      = 0x0700;
-    pub const DETECT_RECEIVER_RSP: u16      = 0x0805;
-    pub const DETECT_RECEIVER_RSP_ACK: u16  = 0x0700;
-    pub const DISPLAY_FRAME: u16            = 0x0107;
-    pub const BRIGHTNESS_BASE: u16          = 0x0A00;
-    pub const PIXEL_ROW_BASE: u16           = 0x5500; 
+/// Errors produced by the transport layer and protocol logic.
+#[derive(Debug)]
+pub enum Error {
+    /// The requested network interface does not exist.
+    InterfaceNotFound(String),
+    /// The underlying [`FrameLink`] failed to send or receive a frame.
+    Link(String),
+    /// No broadcast response was received within the allotted time.
+    Timeout(String),
+    /// A received frame was too short to parse.
+    Truncated,
+    /// A caller-supplied argument was invalid, e.g. an out-of-bounds pixel
+    /// coordinate or a mis-sized buffer.
+    InvalidArgument(String),
+    /// Wraps an I/O error from a `std`-backed transport.
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
 }
 
-#[derive(Debug)]
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InterfaceNotFound(msg) => write!(f, "{}", msg),
+            Error::Link(msg) => write!(f, "{}", msg),
+            Error::Timeout(msg) => write!(f, "{}", msg),
+            Error::Truncated => write!(f, "frame too short to parse"),
+            Error::InvalidArgument(msg) => write!(f, "{}", msg),
+            #[cfg(feature = "std")]
+            Error::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ReceiverCardInfo {
+    /// Source MAC address the card responded from.
+    pub mac: [u8; 6],
     pub version_major: u8,
     pub version_minor: u8,
     pub pixel_columns: u16,
     pub pixel_rows: u16,
 }
 
-pub struct ColorlightCard {
-    tx: Box<dyn pnet::datalink::DataLinkSender>,
-    rx: Box<dyn pnet::datalink::DataLinkReceiver>,
-}
+/// A receiver card found via [`ColorlightCard::detect_all_receivers`],
+/// keyed by the MAC address it responded from when chaining several
+/// receiver cards on the same link.
+pub type DiscoveredCard = ReceiverCardInfo;
 
-impl ColorlightCard {
-    /// opens a raw socket on the given network interface, needs CAP_NET_RAW capability on Linux for example
-    pub fn open(interface_name: &str) -> Result<Self, Error> {
-        let interfaces = datalink::interfaces();
-        let interface = interfaces
-            .into_iter()
-            .find(|iface| iface.name == interface_name)
-            .ok_or_else(|| {
-                Error::new(
-                    ErrorKind::NotFound,
-                    format!("No interface named {}", interface_name),
-                )
-            })?;
-
-        let mut cfg = Config::default();
-        cfg.read_buffer_size = 4096; 
-        cfg.write_buffer_size = 4096;
-
-        let (tx, rx) = match datalink::channel(&interface, cfg)? {
-            Ethernet(tx, rx) => (tx, rx),
-            _ => {
-                return Err(Error::new(
-                    ErrorKind::Other,
-                    "Unsupported channel type (only Ethernet is supported)",
-                ))
-            }
-        };
+/// Drives a Colorlight receiver card over any [`FrameLink`] transport.
+pub struct ColorlightCard<L: FrameLink> {
+    pub(crate) link: L,
+}
 
-        Ok(Self {
-            tx,
-            rx,
-        })
+impl<L: FrameLink> ColorlightCard<L> {
+    /// Wraps an already-open [`FrameLink`] so it can be driven as a
+    /// Colorlight card.
+    pub fn new(link: L) -> Self {
+        Self { link }
     }
 
-    /// Sends the “Detect Receiver Card” frame and waits (optionally) for the broadcast
+    /// Sends the "Detect Receiver Card" frame and waits (optionally) for the broadcast
     /// response (0x0805). Returns parsed `ReceiverCardInfo` if successful.
+    ///
+    /// This bounds the wait on a fixed attempt count rather than a
+    /// wall-clock timeout, since [`FrameLink`] has no clock and this method
+    /// must stay usable on `no_std` targets. `std` users who want a
+    /// wall-clock bound instead (e.g. to cap worst-case latency) should use
+    /// [`detect_receiver_timeout`](Self::detect_receiver_timeout).
     pub fn detect_receiver(&mut self) -> Result<ReceiverCardInfo, Error> {
         // send detect request
         let detect_req = build_detect_receiver_req();
@@ -86,13 +137,10 @@ impl ColorlightCard {
         let mut info: Option<ReceiverCardInfo> = None;
         let max_attempts = 100;
         for _ in 0..max_attempts {
-            if let Ok(packet) = self.rx.next() {
-                if packet.len() >= 14 {
-                    let eth_pkt = EthernetPacket::new(packet).unwrap();
-                    if eth_pkt.get_ethertype().0 == eth_types::DETECT_RECEIVER_RSP {
-                        // Parse the response frame
-                        let data = &packet[14..]; // skip Ethernet header
-                        info = Some(parse_detect_receiver_response(data));
+            if let Ok(packet) = self.link.recv_frame() {
+                if wire::ethertype(packet) == Some(EtherType::DetectReceiverRsp) {
+                    if let Ok(parsed) = parse_detect_receiver_frame(packet) {
+                        info = Some(parsed);
                         break;
                     }
                 }
@@ -100,10 +148,9 @@ impl ColorlightCard {
         }
 
         let card_info = info.ok_or_else(|| {
-            Error::new(
-                ErrorKind::TimedOut,
+            Error::Timeout(String::from(
                 "No broadcast response (eth.type=0x0805) from receiver card",
-            )
+            ))
         })?;
 
         // ack with Data[2] = 1
@@ -113,8 +160,8 @@ impl ColorlightCard {
         Ok(card_info)
     }
 
-    /// Sends a “Display Frame” (EtherType = 0x0107).
-    /// This can also set brightness and color temperature if needed.
+    /// Sends a "Display Frame" (EtherType = 0x0107) to the broadcast
+    /// address. This can also set brightness and color temperature if needed.
     ///
     /// * `brightness` is 0..=0xFF (like 0xff for 100%, 0x40 for ~25%, etc.)
     /// * `(r, g, b)` can adjust color temperature or global color scaling.
@@ -125,36 +172,66 @@ impl ColorlightCard {
         g: u8,
         b: u8,
     ) -> Result<(), Error> {
-        let frame = build_display_frame(brightness, r, g, b);
+        self.send_display_frame_to(DST_MAC, brightness, r, g, b)
+    }
+
+    /// Like [`send_display_frame`](Self::send_display_frame), but addressed
+    /// to a specific card rather than the broadcast address. Useful when
+    /// several receiver cards are chained on the same link (see
+    /// [`detect_all_receivers`](Self::detect_all_receivers)).
+    pub fn send_display_frame_to(
+        &mut self,
+        dst_mac: [u8; 6],
+        brightness: u8,
+        r: u8,
+        g: u8,
+        b: u8,
+    ) -> Result<(), Error> {
+        let frame = build_display_frame(dst_mac, brightness, r, g, b);
         self.send_ethernet_frame(&frame)?;
         Ok(())
     }
 
-    /// Sends a row of pixel data (EtherType = 0x5500 or 0x5501). 
-    /// The data is assumed BGR format. 
-    /// 
+    /// Sends a row of pixel data (EtherType = 0x5500 or 0x5501) to the
+    /// broadcast address. The data is assumed BGR format.
+    ///
     /// The row index can exceed 255, so the top bit sets whether we use 0x5500 or 0x5501.
-    /// Each row frame has a length: 7 bytes of header + row_len * 3 (assuming BGR). 
+    /// Each row frame has a length: 7 bytes of header + row_len * 3 (assuming BGR).
     /// In many panels, row_len might be 128 or 256 pixels wide.
     pub fn send_row(&mut self, row_number: u16, row_data_bgr: &[u8]) -> Result<(), Error> {
-        let frame = build_pixel_row_frame(row_number, row_data_bgr);
+        self.send_row_to(DST_MAC, row_number, row_data_bgr)
+    }
+
+    /// Like [`send_row`](Self::send_row), but addressed to a specific card
+    /// rather than the broadcast address. Useful when several receiver
+    /// cards are chained on the same link (see
+    /// [`detect_all_receivers`](Self::detect_all_receivers)).
+    pub fn send_row_to(
+        &mut self,
+        dst_mac: [u8; 6],
+        row_number: u16,
+        row_data_bgr: &[u8],
+    ) -> Result<(), Error> {
+        let frame = build_pixel_row_frame(dst_mac, row_number, row_data_bgr);
         self.send_ethernet_frame(&frame)?;
         Ok(())
     }
 
-    fn send_ethernet_frame(&mut self, payload: &[u8]) -> Result<(), Error> {
-        self.tx.send_to(payload, None).ok_or_else(|| {
-            Error::new(
-                ErrorKind::Other,
-                "Failed to send raw Ethernet frame using pnet DataLinkSender",
-            )
-        })??;
-        Ok(())
+    pub(crate) fn send_ethernet_frame(&mut self, payload: &[u8]) -> Result<(), Error> {
+        self.link.send_frame(payload)
     }
 }
 
-/// Helper function: Build “Detect Receiver Card” request
-fn build_detect_receiver_req() -> Vec<u8> {
+#[cfg(feature = "std")]
+impl ColorlightCard<PnetLink> {
+    /// Opens a raw socket on the given network interface, needs CAP_NET_RAW capability on Linux for example
+    pub fn open(interface_name: &str) -> Result<Self, Error> {
+        Ok(Self::new(PnetLink::open(interface_name)?))
+    }
+}
+
+/// Helper function: Build "Detect Receiver Card" request
+pub(crate) fn build_detect_receiver_req() -> Vec<u8> {
     let total_len = 14 + 270;
     let mut frame = vec![0u8; total_len];
 
@@ -168,12 +245,12 @@ fn build_detect_receiver_req() -> Vec<u8> {
 }
 
 /// Helper function: Build detect receiver ack (eth.type=0x0700, Data[2] = 1)
-fn build_detect_receiver_ack() -> Vec<u8> {
-    let total_len = 14 + 270; 
+pub(crate) fn build_detect_receiver_ack() -> Vec<u8> {
+    let total_len = 14 + 270;
     let mut frame = vec![0u8; total_len];
 
-    frame[0..6].copy_from_slice(&DST_MAC); 
-    frame[6..12].copy_from_slice(&SRC_MAC); 
+    frame[0..6].copy_from_slice(&DST_MAC);
+    frame[6..12].copy_from_slice(&SRC_MAC);
     frame[12] = 0x07;
     frame[13] = 0x00;
 
@@ -182,95 +259,72 @@ fn build_detect_receiver_ack() -> Vec<u8> {
     frame
 }
 
-/// Parse the 0x0805 “Detect Receiver Response Frame” data into a ReceiverCardInfo
-fn parse_detect_receiver_response(data: &[u8]) -> ReceiverCardInfo {
-    // Data[0] = 0x5A (Receiver card version "5A")
-    // Data[1] = version major
-    // Data[2] = version minor
-    // Data[20..22] = pixel columns (HSB + LSB)
-    // Data[22..24] = pixel rows    (HSB + LSB)
-    if data.len() < 24 {
-        // fallback
-        return ReceiverCardInfo {
-            version_major: 0,
-            version_minor: 0,
-            pixel_columns: 0,
-            pixel_rows: 0,
-        };
-    }
-    let version_major = data[1];
-    let version_minor = data[2];
-    let cols = ((data[20] as u16) << 8) | data[21] as u16;
-    let rows = ((data[22] as u16) << 8) | data[23] as u16;
-
-    ReceiverCardInfo {
-        version_major,
-        version_minor,
-        pixel_columns: cols,
-        pixel_rows: rows,
+/// Parse a received "Detect Receiver Response Frame" (Ethernet header
+/// included) into a `ReceiverCardInfo`, keyed by the responder's source MAC.
+pub(crate) fn parse_detect_receiver_frame(frame: &[u8]) -> Result<ReceiverCardInfo, Error> {
+    if frame.len() < 14 {
+        return Err(Error::Truncated);
     }
+    let mut mac = [0u8; 6];
+    mac.copy_from_slice(&frame[6..12]);
+
+    let view = wire::DetectResponse::new_checked(&frame[14..])?;
+
+    Ok(ReceiverCardInfo {
+        mac,
+        version_major: view.version_major(),
+        version_minor: view.version_minor(),
+        pixel_columns: view.pixel_columns()?,
+        pixel_rows: view.pixel_rows()?,
+    })
 }
 
-/// Build a “display frame” (EtherType = 0x0107, data length = 98).
-/// [21] brightness, [22] = 5, [24..27] brightness for R, G, B.
-fn build_display_frame(brightness: u8, r: u8, g: u8, b: u8) -> Vec<u8> {
-    let total_len = 14 + 98;
+/// Build a "display frame" (EtherType = 0x0107, data length = 98).
+fn build_display_frame(dst_mac: [u8; 6], brightness: u8, r: u8, g: u8, b: u8) -> Vec<u8> {
+    let total_len = 14 + wire::DisplayFrame::<&mut [u8]>::LEN;
     let mut frame = vec![0u8; total_len];
 
-    frame[0..6].copy_from_slice(&DST_MAC);
+    frame[0..6].copy_from_slice(&dst_mac);
     frame[6..12].copy_from_slice(&SRC_MAC);
     frame[12] = 0x01;
     frame[13] = 0x07;
 
-    frame[14 + 21] = brightness;
-    frame[14 + 22] = 5;
-    frame[14 + 24] = r;
-    frame[14 + 25] = g;
-    frame[14 + 26] = b;
+    let mut view = wire::DisplayFrame::new_checked(&mut frame[14..])
+        .expect("frame was sized for DisplayFrame::LEN above");
+    view.set_brightness(brightness);
+    view.set_color(r, g, b);
 
     frame
 }
 
-/// Build a single “pixel row” frame (EtherType = 0x5500 or 0x5501).
-/// Data length is 7 + (3 * pixel_count). Format: 
-/// [0] row LSB
-/// [1] MSB of pixel offset
-/// [2] LSB of pixel offset
-/// [3] MSB of pixel count
-/// [4] LSB of pixel count
-/// [5] 0x08
-/// [6] 0x80 or 0x88
-/// [7..] = the BGR pixel data
-fn build_pixel_row_frame(row_number: u16, row_data_bgr: &[u8]) -> Vec<u8> {
-    let pixel_count = (row_data_bgr.len() / 3) as u16; 
-    let header_len = 7;
-    let data_len = header_len + row_data_bgr.len();
-    let total_len = 14 + data_len;
+/// Build a single "pixel row" frame (EtherType = 0x5500 or 0x5501).
+/// The row index can exceed 255, so the top bit sets whether we use 0x5500 or 0x5501.
+fn build_pixel_row_frame(dst_mac: [u8; 6], row_number: u16, row_data_bgr: &[u8]) -> Vec<u8> {
+    let pixel_count = row_data_bgr.len() / 3;
+    let header_len = wire::PixelRow::<&mut [u8]>::HEADER_LEN;
+    let total_len = 14 + header_len + row_data_bgr.len();
 
     let ethertype = if row_number < 256 {
-        0x5500
+        EtherType::PixelRow
     } else {
-        0x5501
+        EtherType::PixelRowExt
     };
 
     let mut frame = vec![0u8; total_len];
 
-    frame[0..6].copy_from_slice(&DST_MAC);
+    frame[0..6].copy_from_slice(&dst_mac);
     frame[6..12].copy_from_slice(&SRC_MAC);
+    let ethertype: u16 = ethertype.into();
     frame[12] = (ethertype >> 8) as u8;
     frame[13] = (ethertype & 0xff) as u8;
 
-    let data_offset = 14;
-    frame[data_offset + 0] = (row_number & 0xff) as u8;
-    frame[data_offset + 1] = 0x00;
-    frame[data_offset + 2] = 0x00;
-    frame[data_offset + 3] = ((pixel_count >> 8) & 0xff) as u8;
-    frame[data_offset + 4] = (pixel_count & 0xff) as u8;
-    frame[data_offset + 5] = 0x08;
-    frame[data_offset + 6] = 0x88; 
-
-    frame[(data_offset + header_len)..(data_offset + header_len + row_data_bgr.len())]
-        .copy_from_slice(&row_data_bgr);
+    let mut view = wire::PixelRow::new_checked(&mut frame[14..], pixel_count)
+        .expect("frame was sized for header_len + pixel_count * 3 above");
+    view.set_row_number(row_number);
+    view.set_pixel_offset(0);
+    view.set_pixel_count(pixel_count as u16);
+    view.set_flags();
+    view.set_pixel_data(row_data_bgr);
 
     frame
 }
@@ -0,0 +1,105 @@
+//! Transport abstraction that decouples the Colorlight wire protocol from
+//! the physical link frames travel over.
+//!
+//! [`FrameLink`] is the only thing the protocol logic in [`crate`] needs:
+//! something that can push a raw Ethernet frame out and hand a raw Ethernet
+//! frame back. This mirrors how smoltcp/jnet keep their protocol stacks
+//! agnostic of the PHY underneath, so the same frame-building code can drive
+//! a panel from a libpnet raw socket on a desktop, or from a bare "send
+//! these bytes on the wire" primitive on a microcontroller.
+
+use crate::Error;
+
+/// A bidirectional raw Ethernet frame transport.
+///
+/// Implementations hand back whole Ethernet frames (header included) from
+/// [`recv_frame`](FrameLink::recv_frame), and accept whole Ethernet frames
+/// (header included) in [`send_frame`](FrameLink::send_frame).
+pub trait FrameLink {
+    /// Sends a complete Ethernet frame (header included) on the link.
+    fn send_frame(&mut self, frame: &[u8]) -> Result<(), Error>;
+
+    /// Waits for the next Ethernet frame (header included) on the link and
+    /// returns it. An implementation may return an error when no frame
+    /// arrives within its own read timeout, as `PnetLink` does; the
+    /// receive loops in [`crate`] treat such an error as "no frame yet".
+    fn recv_frame(&mut self) -> Result<&[u8], Error>;
+}
+
+#[cfg(feature = "std")]
+mod pnet_link {
+    use super::FrameLink;
+    use crate::Error;
+    use alloc::string::String;
+    use pnet::datalink::Channel::Ethernet;
+    use pnet::datalink::{self, Config};
+    use std::time::Duration;
+
+    /// Default [`FrameLink`] backed by libpnet raw Ethernet sockets.
+    ///
+    /// This is what [`ColorlightCard::open`](crate::ColorlightCard::open)
+    /// uses on `std` targets.
+    pub struct PnetLink {
+        tx: alloc::boxed::Box<dyn datalink::DataLinkSender>,
+        rx: alloc::boxed::Box<dyn datalink::DataLinkReceiver>,
+    }
+
+    impl PnetLink {
+        /// Opens a raw socket on the given network interface, needs
+        /// CAP_NET_RAW capability on Linux for example.
+        pub fn open(interface_name: &str) -> Result<Self, Error> {
+            let interfaces = datalink::interfaces();
+            let interface = interfaces
+                .into_iter()
+                .find(|iface| iface.name == interface_name)
+                .ok_or_else(|| {
+                    Error::InterfaceNotFound(alloc::format!(
+                        "No interface named {}",
+                        interface_name
+                    ))
+                })?;
+
+            let cfg = Config {
+                read_buffer_size: 4096,
+                write_buffer_size: 4096,
+                // Without a read timeout, `recv_frame` blocks in the
+                // underlying `rx.next()` forever when the link goes quiet,
+                // which would defeat any `Instant`/`Duration` deadline a
+                // caller (e.g. `detect_all_receivers`) builds on top of it.
+                read_timeout: Some(Duration::from_millis(100)),
+                ..Default::default()
+            };
+
+            let (tx, rx) = match datalink::channel(&interface, cfg)? {
+                Ethernet(tx, rx) => (tx, rx),
+                _ => {
+                    return Err(Error::Link(String::from(
+                        "Unsupported channel type (only Ethernet is supported)",
+                    )))
+                }
+            };
+
+            Ok(Self { tx, rx })
+        }
+    }
+
+    impl FrameLink for PnetLink {
+        fn send_frame(&mut self, frame: &[u8]) -> Result<(), Error> {
+            self.tx
+                .send_to(frame, None)
+                .ok_or_else(|| {
+                    Error::Link(String::from(
+                        "Failed to send raw Ethernet frame using pnet DataLinkSender",
+                    ))
+                })??;
+            Ok(())
+        }
+
+        fn recv_frame(&mut self) -> Result<&[u8], Error> {
+            Ok(self.rx.next()?)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub use pnet_link::PnetLink;
@@ -1,53 +1,164 @@
-#[cfg(test)]
-mod tests {
-    use crate::*;
-
-    #[test]
-    fn test_build_detect_receiver_req() {
-        let frame = build_detect_receiver_req();
-        assert_eq!(frame.len(), 284);
-        assert_eq!(&frame[0..6], &DST_MAC);
-        assert_eq!(&frame[6..12], &SRC_MAC);
-        assert_eq!(frame[12], 0x07);
-        assert_eq!(frame[13], 0x00);
-    }
+use crate::*;
+
+#[test]
+fn test_build_detect_receiver_req() {
+    let frame = build_detect_receiver_req();
+    assert_eq!(frame.len(), 284);
+    assert_eq!(&frame[0..6], &DST_MAC);
+    assert_eq!(&frame[6..12], &SRC_MAC);
+    assert_eq!(frame[12], 0x07);
+    assert_eq!(frame[13], 0x00);
+}
+
+#[test]
+fn test_build_detect_receiver_ack() {
+    let frame = build_detect_receiver_ack();
+    assert_eq!(frame.len(), 284);
+    assert_eq!(&frame[0..6], &DST_MAC);
+    assert_eq!(&frame[6..12], &SRC_MAC);
+    assert_eq!(frame[12], 0x07);
+    assert_eq!(frame[13], 0x00);
+    assert_eq!(frame[16], 1);
+}
+
+#[test]
+fn test_parse_detect_receiver_frame() {
+    let mut frame = vec![0u8; 14];
+    frame[0..6].copy_from_slice(&DST_MAC);
+    frame[6..12].copy_from_slice(&[0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF]);
+    frame[12] = 0x08;
+    frame[13] = 0x05;
+    frame.extend_from_slice(&[
+        0x5A, 0x01, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x01, 0x00, 0x02, 0x00,
+    ]);
+
+    let info = parse_detect_receiver_frame(&frame).unwrap();
+    assert_eq!(info.mac, [0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF]);
+    assert_eq!(info.version_major, 1);
+    assert_eq!(info.version_minor, 2);
+    assert_eq!(info.pixel_columns, 256);
+    assert_eq!(info.pixel_rows, 512);
+}
+
+#[test]
+fn test_parse_detect_receiver_frame_truncated() {
+    let frame = vec![0u8; 14];
+    assert!(parse_detect_receiver_frame(&frame).is_err());
+}
 
-    #[test]
-    fn test_build_detect_receiver_ack() {
-        let frame = build_detect_receiver_ack();
-        assert_eq!(frame.len(), 284);
-        assert_eq!(&frame[0..6], &DST_MAC);
-        assert_eq!(&frame[6..12], &SRC_MAC);
-        assert_eq!(frame[12], 0x07);
-        assert_eq!(frame[13], 0x00);
-        assert_eq!(frame[16], 1);
+#[test]
+fn test_build_display_frame() {
+    let frame = build_display_frame(DST_MAC, 0xFF, 0xFF, 0x76, 0x06);
+    assert_eq!(frame.len(), 112);
+    assert_eq!(&frame[0..6], &DST_MAC);
+    assert_eq!(&frame[6..12], &SRC_MAC);
+    assert_eq!(frame[12], 0x01);
+    assert_eq!(frame[13], 0x07);
+    assert_eq!(frame[35], 0xFF);
+    assert_eq!(frame[36], 5);
+    assert_eq!(frame[38], 0xFF);
+    assert_eq!(frame[39], 0x76);
+    assert_eq!(frame[40], 0x06);
+}
+
+/// [`FrameLink`] that just records every frame handed to `send_frame`, so
+/// tests can inspect what a [`Framebuffer`] flush actually put on the
+/// wire. Never has anything to receive.
+struct MockLink {
+    sent: Vec<Vec<u8>>,
+}
+
+impl MockLink {
+    fn new() -> Self {
+        Self { sent: Vec::new() }
     }
+}
 
-    #[test]
-    fn test_parse_detect_receiver_response() {
-        let data = vec![
-            0x5A, 0x01, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x01, 0x00, 0x02, 0x00,
-        ];
-        let info = parse_detect_receiver_response(&data);
-        assert_eq!(info.version_major, 1);
-        assert_eq!(info.version_minor, 2);
-        assert_eq!(info.pixel_columns, 256);
-        assert_eq!(info.pixel_rows, 512);
+impl FrameLink for MockLink {
+    fn send_frame(&mut self, frame: &[u8]) -> Result<(), Error> {
+        self.sent.push(frame.to_vec());
+        Ok(())
     }
 
-    #[test]
-    fn test_build_display_frame() {
-        let frame = build_display_frame(0xFF, 0xFF, 0x76, 0x06);
-        assert_eq!(frame.len(), 112);
-        assert_eq!(&frame[0..6], &DST_MAC);
-        assert_eq!(&frame[6..12], &SRC_MAC);
-        assert_eq!(frame[12], 0x01);
-        assert_eq!(frame[13], 0x07);
-        assert_eq!(frame[35], 0xFF);
-        assert_eq!(frame[36], 5);
-        assert_eq!(frame[38], 0xFF);
-        assert_eq!(frame[39], 0x76);
-        assert_eq!(frame[40], 0x06);
+    fn recv_frame(&mut self) -> Result<&[u8], Error> {
+        Err(Error::Timeout(String::from("MockLink has nothing queued")))
     }
-}
\ No newline at end of file
+}
+
+// Pixel data for row `y` starts right after the 14-byte Ethernet header
+// and the 7-byte PixelRow header built by `build_pixel_row_frame`.
+const PIXEL_ROW_DATA_OFFSET: usize = 14 + 7;
+
+/// Row frames sent to `card`'s link, in order, as just their pixel data.
+fn sent_row_pixel_data(card: &ColorlightCard<MockLink>) -> Vec<&[u8]> {
+    card.link
+        .sent
+        .iter()
+        .filter(|frame| {
+            matches!(
+                wire::ethertype(frame),
+                Some(EtherType::PixelRow) | Some(EtherType::PixelRowExt)
+            )
+        })
+        .map(|frame| &frame[PIXEL_ROW_DATA_OFFSET..])
+        .collect()
+}
+
+#[test]
+fn test_flush_skips_unchanged_rows() {
+    let mut card = ColorlightCard::new(MockLink::new());
+    let mut fb = Framebuffer::new(2, 2);
+
+    fb.flush(&mut card, 0xFF, 0xFF, 0xFF, 0xFF).unwrap();
+    assert_eq!(sent_row_pixel_data(&card).len(), 2);
+
+    card.link.sent.clear();
+    fb.flush(&mut card, 0xFF, 0xFF, 0xFF, 0xFF).unwrap();
+    assert_eq!(sent_row_pixel_data(&card).len(), 0);
+}
+
+#[test]
+fn test_flush_resends_only_changed_row() {
+    let mut card = ColorlightCard::new(MockLink::new());
+    let mut fb = Framebuffer::new(2, 2);
+    fb.flush(&mut card, 0xFF, 0xFF, 0xFF, 0xFF).unwrap();
+
+    fb.set_pixel(0, 1, [0x10, 0x20, 0x30]).unwrap();
+    card.link.sent.clear();
+    fb.flush(&mut card, 0xFF, 0xFF, 0xFF, 0xFF).unwrap();
+
+    let rows = sent_row_pixel_data(&card);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0][0..6], [0x30, 0x20, 0x10, 0, 0, 0]);
+}
+
+#[test]
+fn test_row_bgr_applies_gamma_table() {
+    // gamma=1.0 leaves the channel unscaled; brightness=0.5 halves it.
+    // R: 0xFF/255 * 1.0 * 0.5 * 255 = 127.5, rounds to 128. G and B are
+    // identity (gamma=1.0, brightness=1.0), so they pass through as-is.
+    let gamma = GammaTable::per_channel((1.0, 0.5), (1.0, 1.0), (1.0, 1.0));
+    let mut card = ColorlightCard::new(MockLink::new());
+    let mut fb = Framebuffer::with_gamma(1, 1, gamma);
+    fb.set_pixel(0, 0, [0xFF, 0x80, 0x40]).unwrap();
+
+    fb.flush(&mut card, 0xFF, 0xFF, 0xFF, 0xFF).unwrap();
+
+    // BGR-swizzled: blue, green, red.
+    let rows = sent_row_pixel_data(&card);
+    assert_eq!(rows[0][0..3], [0x40, 0x80, 128]);
+}
+
+#[test]
+fn test_set_gamma_forces_full_retransmit() {
+    let mut card = ColorlightCard::new(MockLink::new());
+    let mut fb = Framebuffer::new(2, 2);
+    fb.flush(&mut card, 0xFF, 0xFF, 0xFF, 0xFF).unwrap();
+
+    card.link.sent.clear();
+    fb.set_gamma(GammaTable::new(1.0, 0.5));
+    fb.flush(&mut card, 0xFF, 0xFF, 0xFF, 0xFF).unwrap();
+
+    assert_eq!(sent_row_pixel_data(&card).len(), 2);
+}
@@ -0,0 +1,225 @@
+//! Zero-copy, bounds-checked views over Colorlight protocol frames.
+//!
+//! Modeled on smoltcp's `wire` module: instead of builders and parsers
+//! poking raw byte offsets directly, each frame shape gets a typed view
+//! wrapping a buffer (`&[u8]` to read, `&mut [u8]` to write) with named,
+//! checked accessors. [`EtherType`] follows smoltcp's `enum_with_unknown!`
+//! pattern so unrecognised ethertypes round-trip instead of panicking.
+
+use crate::Error;
+
+macro_rules! enum_with_unknown {
+    (
+        $( #[$attr:meta] )*
+        pub enum $name:ident($repr:ty) {
+            $( $variant:ident = $value:expr ),+ $(,)?
+        }
+    ) => {
+        $( #[$attr] )*
+        pub enum $name {
+            $( $variant, )+
+            Unknown($repr),
+        }
+
+        impl ::core::convert::From<$repr> for $name {
+            fn from(value: $repr) -> Self {
+                match value {
+                    $( $value => $name::$variant, )+
+                    other => $name::Unknown(other),
+                }
+            }
+        }
+
+        impl ::core::convert::From<$name> for $repr {
+            fn from(value: $name) -> Self {
+                match value {
+                    $( $name::$variant => $value, )+
+                    $name::Unknown(other) => other,
+                }
+            }
+        }
+    };
+}
+
+enum_with_unknown! {
+    /// EtherTypes (in the Colorlight-specific sense, carried in the same
+    /// header field as a real 802.3 ethertype) used by the wire protocol.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum EtherType(u16) {
+        DetectReceiverReq = 0x0700,
+        DetectReceiverRsp = 0x0805,
+        DisplayFrame = 0x0107,
+        PixelRow = 0x5500,
+        PixelRowExt = 0x5501,
+    }
+}
+
+/// Ethernet header length: 6-byte dst MAC + 6-byte src MAC + 2-byte
+/// ethertype.
+pub const ETH_HEADER_LEN: usize = 14;
+
+/// Reads the ethertype out of a full Ethernet frame (header included),
+/// returning `None` if `frame` is too short to contain one.
+pub fn ethertype(frame: &[u8]) -> Option<EtherType> {
+    let bytes = frame.get(12..ETH_HEADER_LEN)?;
+    Some(EtherType::from(((bytes[0] as u16) << 8) | bytes[1] as u16))
+}
+
+fn read_be16(buf: &[u8], offset: usize) -> Result<u16, Error> {
+    let bytes = buf.get(offset..offset + 2).ok_or(Error::Truncated)?;
+    Ok(((bytes[0] as u16) << 8) | bytes[1] as u16)
+}
+
+fn write_be16(buf: &mut [u8], offset: usize, value: u16) {
+    buf[offset] = (value >> 8) as u8;
+    buf[offset + 1] = (value & 0xff) as u8;
+}
+
+/// A checked, read-only view over a "Detect Receiver Response" payload
+/// (EtherType 0x0805, Ethernet header already stripped).
+///
+/// ```text
+/// Data[0]      = 0x5A (receiver card version "5A")
+/// Data[1]      = version major
+/// Data[2]      = version minor
+/// Data[20..22] = pixel columns (MSB, LSB)
+/// Data[22..24] = pixel rows    (MSB, LSB)
+/// ```
+pub struct DetectResponse<T: AsRef<[u8]>> {
+    buffer: T,
+}
+
+impl<T: AsRef<[u8]>> DetectResponse<T> {
+    /// Minimum payload length this view can be read from.
+    pub const MIN_LEN: usize = 24;
+
+    /// Wraps `buffer`, checking it is long enough to hold every field.
+    pub fn new_checked(buffer: T) -> Result<Self, Error> {
+        if buffer.as_ref().len() < Self::MIN_LEN {
+            return Err(Error::Truncated);
+        }
+        Ok(Self { buffer })
+    }
+
+    /// Single-byte field already covered by `new_checked`'s length check, so
+    /// this returns the byte directly rather than a `Result` like
+    /// [`pixel_columns`](Self::pixel_columns).
+    pub fn version_major(&self) -> u8 {
+        self.buffer.as_ref()[1]
+    }
+
+    /// Single-byte field already covered by `new_checked`'s length check, so
+    /// this returns the byte directly rather than a `Result` like
+    /// [`pixel_columns`](Self::pixel_columns).
+    pub fn version_minor(&self) -> u8 {
+        self.buffer.as_ref()[2]
+    }
+
+    pub fn pixel_columns(&self) -> Result<u16, Error> {
+        read_be16(self.buffer.as_ref(), 20)
+    }
+
+    pub fn pixel_rows(&self) -> Result<u16, Error> {
+        read_be16(self.buffer.as_ref(), 22)
+    }
+}
+
+/// A checked, writable view over a "Display Frame" payload
+/// (EtherType 0x0107, Ethernet header already stripped).
+///
+/// ```text
+/// Data[21]     = brightness
+/// Data[22]     = 5 (fixed marker)
+/// Data[24..27] = color temperature / scaling for R, G, B
+/// ```
+pub struct DisplayFrame<T: AsRef<[u8]> + AsMut<[u8]>> {
+    buffer: T,
+}
+
+impl<T: AsRef<[u8]> + AsMut<[u8]>> DisplayFrame<T> {
+    /// Fixed payload length of a display frame.
+    pub const LEN: usize = 98;
+
+    /// Wraps `buffer`, checking it is long enough to hold every field.
+    pub fn new_checked(buffer: T) -> Result<Self, Error> {
+        if buffer.as_ref().len() < Self::LEN {
+            return Err(Error::Truncated);
+        }
+        Ok(Self { buffer })
+    }
+
+    pub fn set_brightness(&mut self, brightness: u8) {
+        let buf = self.buffer.as_mut();
+        buf[21] = brightness;
+        buf[22] = 5;
+    }
+
+    pub fn set_color(&mut self, r: u8, g: u8, b: u8) {
+        let buf = self.buffer.as_mut();
+        buf[24] = r;
+        buf[25] = g;
+        buf[26] = b;
+    }
+
+    /// Unwraps the view, returning the underlying buffer.
+    pub fn into_inner(self) -> T {
+        self.buffer
+    }
+}
+
+/// A checked, writable view over a "Pixel Row" payload
+/// (EtherType 0x5500/0x5501, Ethernet header already stripped).
+///
+/// ```text
+/// Data[0]    = row number LSB
+/// Data[1..3] = pixel offset (MSB, LSB)
+/// Data[3..5] = pixel count  (MSB, LSB)
+/// Data[5]    = 0x08
+/// Data[6]    = 0x80 or 0x88
+/// Data[7..]  = BGR pixel data
+/// ```
+pub struct PixelRow<T: AsRef<[u8]> + AsMut<[u8]>> {
+    buffer: T,
+}
+
+impl<T: AsRef<[u8]> + AsMut<[u8]>> PixelRow<T> {
+    /// Length of the fixed header preceding the pixel data.
+    pub const HEADER_LEN: usize = 7;
+
+    /// Wraps `buffer`, checking it is long enough to hold the header plus
+    /// `pixel_count` BGR pixels.
+    pub fn new_checked(buffer: T, pixel_count: usize) -> Result<Self, Error> {
+        if buffer.as_ref().len() < Self::HEADER_LEN + pixel_count * 3 {
+            return Err(Error::Truncated);
+        }
+        Ok(Self { buffer })
+    }
+
+    pub fn set_row_number(&mut self, row_number: u16) {
+        self.buffer.as_mut()[0] = (row_number & 0xff) as u8;
+    }
+
+    pub fn set_pixel_offset(&mut self, offset: u16) {
+        write_be16(self.buffer.as_mut(), 1, offset);
+    }
+
+    pub fn set_pixel_count(&mut self, count: u16) {
+        write_be16(self.buffer.as_mut(), 3, count);
+    }
+
+    pub fn set_flags(&mut self) {
+        let buf = self.buffer.as_mut();
+        buf[5] = 0x08;
+        buf[6] = 0x88;
+    }
+
+    pub fn set_pixel_data(&mut self, data_bgr: &[u8]) {
+        let start = Self::HEADER_LEN;
+        self.buffer.as_mut()[start..start + data_bgr.len()].copy_from_slice(data_bgr);
+    }
+
+    /// Unwraps the view, returning the underlying buffer.
+    pub fn into_inner(self) -> T {
+        self.buffer
+    }
+}